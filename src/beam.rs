@@ -0,0 +1,153 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/**
+ * A width-bounded search over key candidates, using the DictTree prefix
+ * structure to prune keys as they're built offset by offset instead of
+ * enumerating the full AttemptOrder combination space
+ */
+
+use crate::dict_tree::DictTree;
+use crate::{decrypt_char, Encoded};
+
+const BEAM_WIDTH: usize = 25;
+
+/**
+ * A partial key, holding the offsets chosen so far, a cumulative score
+ * (minus the sum of the chosen offsets' frequency ranks, standing in for a
+ * log-probability), and how many characters from the very start are
+ * trie-confirmed dictionary prefix.
+ *
+ * verified_len freezes the moment a candidate's prefix first stops
+ * matching the trie. Candidates are ordered by score alone; verified_len
+ * only decides which extensions get pruned.
+ */
+struct Candidate {
+	key: Vec<u8>,
+	score: f32,
+	verified_len: usize,
+}
+
+impl PartialEq for Candidate {
+	fn eq(&self, other: &Self) -> bool {
+		self.score == other.score
+	}
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for Candidate {
+	fn cmp(&self, other: &Self) -> Ordering {
+		self.score.partial_cmp(&other.score).unwrap_or(Ordering::Equal)
+	}
+}
+
+pub struct BeamSearch<'a> {
+	ciphertext: &'a Encoded,
+	pw_len: usize,
+	best_keys: &'a Vec<Vec<u8>>,
+	tree: &'a DictTree,
+}
+
+impl<'a> BeamSearch<'a> {
+	/**
+	 * Builds a new beam search over the given ciphertext and offset
+	 * rankings, pruning against the given dictionary trie
+	 */
+	pub fn new(ciphertext: &'a Encoded, pw_len: usize, best_keys: &'a Vec<Vec<u8>>, tree: &'a DictTree) -> Self {
+		BeamSearch { ciphertext, pw_len, best_keys, tree }
+	}
+
+	/**
+	 * Runs the beam search and returns the highest-scoring complete key
+	 * left in the final beam, or None if every extension was pruned.
+	 *
+	 * A leading word shorter than pw_len makes the decrypted prefix cross
+	 * into a second word, so strictly requiring a trie prefix the whole
+	 * way through would make the beam run dry on most real sentences.
+	 * Instead each candidate keeps extending its trie-confirmed prefix for
+	 * as long as the next offset keeps it inside the trie; once an
+	 * extension falls outside the trie, it's kept only if its
+	 * trie-confirmed prefix already begins with a full dictionary word (the
+	 * word boundary has been crossed) and dropped otherwise. A kept
+	 * candidate's verified_len then freezes and the remaining positions for
+	 * it are chosen purely by the offsets' frequency rank. Each step keeps
+	 * the BEAM_WIDTH best candidates by score.
+	 */
+	pub fn run(&self) -> Option<Encoded> {
+		let mut beam = vec![Candidate { key: Vec::new(), score: 0.0, verified_len: 0 }];
+
+		for position in 0..self.pw_len {
+			let mut heap = BinaryHeap::new();
+
+			for candidate in &beam {
+				for (rank, &offset) in self.best_keys[position].iter().enumerate() {
+					let mut key = candidate.key.clone();
+					key.push(offset);
+
+					// Better-ranked offsets stand in for a higher
+					// log-probability score
+					let score = candidate.score - rank as f32;
+
+					let verified_len = if candidate.verified_len == position {
+						let prefix = key.iter().enumerate()
+							.map(|(i, o)| decrypt_char(&self.ciphertext[i], o))
+							.collect::<Encoded>();
+
+						if self.tree.is_prefix(&prefix) {
+							position + 1
+						} else if (1..=position).any(|len| self.tree.contains(&prefix[..len].to_vec())) {
+							candidate.verified_len
+						} else {
+							continue;
+						}
+					} else {
+						candidate.verified_len
+					};
+
+					heap.push(Candidate { key, score, verified_len });
+				}
+			}
+
+			beam = heap.into_sorted_vec().into_iter().rev().take(BEAM_WIDTH).collect();
+		}
+
+		beam.into_iter().max().map(|candidate| candidate.key)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Dict;
+	use crate::test_utils::{encode, encrypt};
+
+	#[test]
+	fn finds_key_when_leading_word_is_shorter_than_pw_len() {
+		// "hello" is 5 letters, shorter than the 7-letter key, so the
+		// decrypted prefix crosses into "world" before the key repeats
+		let dict: Dict = vec!["hello", "world", "secret"].iter().map(|w| encode(w)).collect();
+		let tree = DictTree::new(&dict);
+
+		let key = encode("keyword");
+		let ciphertext = encrypt(&encode("helloworldsecret"), &key);
+
+		let best_keys: Vec<Vec<u8>> = (0..key.len())
+			.map(|i| {
+				let mut offsets: Vec<u8> = (0..26).collect();
+				offsets.swap(0, key[i] as usize);
+				offsets
+			})
+			.collect();
+
+		let beam = BeamSearch::new(&ciphertext, key.len(), &best_keys, &tree);
+
+		assert_eq!(beam.run(), Some(key));
+	}
+}
@@ -0,0 +1,166 @@
+use std::fs::File;
+use std::io::{Read, Write};
+
+/**
+ * A compact, order-preserving binary codec for the dictionary and
+ * frequency cache files, replacing the serde_json ones that stored every
+ * byte as ASCII digits
+ *
+ * Each file starts with a magic header and version byte for forward
+ * compatibility, followed by a one-byte tag identifying its payload. Words
+ * are written length-prefixed, and frequencies are written as
+ * IEEE-ordered floats so a byte-wise compare matches numeric order.
+ */
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+
+type Encoded = Vec<u8>;
+type Dict = Vec<Encoded>;
+
+const MAGIC: u32 = 0x5643_4b31; // "VCK1"
+const VERSION: u8 = 1;
+
+const TAG_WORDS: u8 = 1;
+const TAG_FREQS: u8 = 2;
+
+/**
+ * Flips an f32's bit pattern so byte comparison of the result matches
+ * numeric comparison of the float
+ */
+fn order_f32(value: f32) -> u32 {
+	let bits = value.to_bits();
+
+	if bits & 0x8000_0000 != 0 { !bits } else { bits | 0x8000_0000 }
+}
+
+fn unorder_f32(ordered: u32) -> f32 {
+	let bits = if ordered & 0x8000_0000 != 0 { ordered & !0x8000_0000 } else { !ordered };
+
+	f32::from_bits(bits)
+}
+
+fn write_header<W: Write>(writer: &mut W, tag: u8) -> std::io::Result<()> {
+	writer.write_u32::<BigEndian>(MAGIC)?;
+	writer.write_u8(VERSION)?;
+	writer.write_u8(tag)
+}
+
+fn read_header<R: Read>(reader: &mut R, expected_tag: u8) -> std::io::Result<()> {
+	let magic = reader.read_u32::<BigEndian>()?;
+	let version = reader.read_u8()?;
+	let tag = reader.read_u8()?;
+
+	if magic != MAGIC || version != VERSION || tag != expected_tag {
+		return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "bad cache header"));
+	}
+
+	Ok(())
+}
+
+/**
+ * Writes the whole encoded dictionary cache file, sorted for a stable
+ * on-disk layout
+ */
+pub fn write_dict(dict: &Dict) -> std::io::Result<()> {
+	let mut sorted = dict.clone();
+	sorted.sort();
+
+	let mut file = File::create("dict.bin")?;
+
+	write_header(&mut file, TAG_WORDS)?;
+	file.write_u32::<BigEndian>(sorted.len() as u32)?;
+
+	for word in &sorted {
+		file.write_u8(word.len() as u8)?;
+		file.write_all(word)?;
+	}
+
+	Ok(())
+}
+
+/**
+ * Reads the whole encoded dictionary cache file written by write_dict
+ */
+pub fn read_dict() -> std::io::Result<Dict> {
+	let mut file = File::open("dict.bin")?;
+
+	read_header(&mut file, TAG_WORDS)?;
+	let count = file.read_u32::<BigEndian>()?;
+
+	let mut dict = Vec::with_capacity(count as usize);
+
+	for _ in 0..count {
+		let len = usize::from(file.read_u8()?);
+		let mut word = vec![0u8; len];
+		file.read_exact(&mut word)?;
+		dict.push(word);
+	}
+
+	Ok(dict)
+}
+
+/**
+ * Writes the letter frequency cache file, one IEEE-ordered float per letter
+ */
+pub fn write_dict_freqs(freqs: &Vec<f32>) -> std::io::Result<()> {
+	let mut file = File::create("frequencies.bin")?;
+
+	write_header(&mut file, TAG_FREQS)?;
+	file.write_u32::<BigEndian>(freqs.len() as u32)?;
+
+	for freq in freqs {
+		file.write_u32::<BigEndian>(order_f32(*freq))?;
+	}
+
+	Ok(())
+}
+
+/**
+ * Reads the letter frequency cache file written by write_dict_freqs
+ */
+pub fn read_dict_freqs() -> std::io::Result<Vec<f32>> {
+	let mut file = File::open("frequencies.bin")?;
+
+	read_header(&mut file, TAG_FREQS)?;
+	let count = file.read_u32::<BigEndian>()?;
+
+	let mut freqs = Vec::with_capacity(count as usize);
+
+	for _ in 0..count {
+		let ordered = file.read_u32::<BigEndian>()?;
+		freqs.push(unorder_f32(ordered));
+	}
+
+	Ok(freqs)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn dict_round_trips_words_of_any_length() {
+		let dict: Dict = vec![vec![7, 4, 11, 11, 14], vec![2, 4, 0, 17]];
+
+		write_dict(&dict).unwrap();
+		let read_back = read_dict().unwrap();
+
+		let mut sorted = dict;
+		sorted.sort();
+		assert_eq!(read_back, sorted);
+
+		std::fs::remove_file("dict.bin").unwrap();
+	}
+
+	#[test]
+	fn dict_freqs_round_trip() {
+		let freqs = vec![0.1, 0.2, -0.05, 0.0];
+
+		write_dict_freqs(&freqs).unwrap();
+		let read_back = read_dict_freqs().unwrap();
+
+		assert_eq!(read_back, freqs);
+
+		std::fs::remove_file("frequencies.bin").unwrap();
+	}
+}
@@ -1,102 +1,244 @@
-use std::collections::HashMap;
+use std::collections::VecDeque;
 
 /**
- * A representation of a dictionary's possible words as a tree of letters
+ * A representation of a dictionary's possible words as a double-array trie
+ *
+ * Transitions are stored as two parallel arrays: for state `s` taking
+ * letter `c` (0-25), the next state is `t = base[s] + c`, and the
+ * transition is only valid if `check[t] == s`. This walks as plain array
+ * indexing with no pointer-chasing, and the whole structure is just two
+ * flat integer arrays to serialize.
  */
 
-use serde::{Serialize, ser::SerializeSeq};
+use serde::{Deserialize, Serialize};
 
+type Encoded = Vec<u8>;
+type Dict = Vec<Vec<u8>>;
+
+const ALPHABET: i32 = 26;
+const ROOT: usize = 0;
+
+// A state with no children never gets a base assigned by compact(), so it
+// starts out at this sentinel instead of 0 - leaving it at 0 would only
+// "work" by coincidence, since slot 0 (the root's own cell) happens to
+// never be a transition target. Walking past a leaf always adds a
+// non-negative offset, so this stays far enough out of check's bounds
+// that the walk fails instead of landing on an unrelated cell.
+const INVALID_BASE: i32 = i32::MIN / 2;
+
+#[derive(Serialize, Deserialize)]
 pub struct DictTree {
-	head: Node,
+	base: Vec<i32>,
+	check: Vec<i32>,
+	terminal: Vec<bool>,
 }
 
-struct Node {
-	children: Vec<Option<Box<Node>>>
+/**
+ * A node in the scratch trie the words are collected into before it's
+ * compacted down into the double array
+ */
+struct ScratchNode {
+	children: [Option<usize>; 26],
+	is_word: bool,
 }
 
-type Encoded = Vec<u8>;
-type Dict = Vec<Vec<u8>>;
+impl ScratchNode {
+	fn new() -> Self {
+		ScratchNode { children: [None; 26], is_word: false }
+	}
+}
 
 impl DictTree {
 	/**
 	 * Creates a new DictTree from a Dict
 	 */
 	pub fn new(dict: &Dict) -> Self {
-		let children = std::iter::repeat_with(|| None).take(26).collect();
-		let mut head = Node{children};
+		let mut scratch = vec![ScratchNode::new()];
 
-		// Add all the words
 		for word in dict {
-			let mut word = word.clone();
-			head.add(&mut word);
+			let mut state = ROOT;
+
+			for &c in word {
+				let index = usize::from(c);
+
+				state = match scratch[state].children[index] {
+					Some(next) => next,
+					None => {
+						scratch.push(ScratchNode::new());
+						let next = scratch.len() - 1;
+						scratch[state].children[index] = Some(next);
+						next
+					}
+				};
+			}
+
+			scratch[state].is_word = true;
 		}
 
-		DictTree{head}
+		Self::compact(&scratch)
 	}
 
 	/**
-	 * Find out if a string exists in this dictionary tree
+	 * Lays the scratch trie out as a double array. Each state's children
+	 * are placed at the first base offset where all of them land in free
+	 * cells; scanning forward for a free offset is simpler than relocating
+	 * an already-placed family, at the cost of some unused array space.
 	 */
-	pub fn contains(&self, string: &Encoded) -> bool {
-		let mut str = string.clone();
-		self.head.exists(&mut str)
-	}
-}
+	fn compact(scratch: &Vec<ScratchNode>) -> Self {
+		let mut base = vec![INVALID_BASE; scratch.len() + ALPHABET as usize];
+		let mut check = vec![-1; scratch.len() + ALPHABET as usize];
+		let mut terminal = vec![false; scratch.len() + ALPHABET as usize];
 
-impl Node {
-	/**
-	 * Find out if a string exists in this dictionary tree
-	 */
-	fn exists(&self, string: &mut Encoded) -> bool {
-		if string.len() == 1 {
-			let index = usize::from(string[0]);
-			self.children[index].is_some()
-		} else {
-			let front = string.remove(0);
-			let index = usize::from(front);
-
-			match &self.children[index] {
-				Some(node) => node.exists(string),
-				None => false
+		// Maps a scratch-trie state to its landing cell in the double array
+		let mut mapped = vec![ROOT; scratch.len()];
+		let mut next_free = ROOT + 1;
+
+		let mut queue = VecDeque::new();
+		queue.push_back(ROOT);
+		terminal[ROOT] = scratch[ROOT].is_word;
+
+		while let Some(scratch_state) = queue.pop_front() {
+			let state = mapped[scratch_state];
+			let node = &scratch[scratch_state];
+
+			let children: Vec<(i32, usize)> = node.children.iter().enumerate()
+				.filter_map(|(c, child)| child.map(|child| (c as i32, child)))
+				.collect();
+
+			if children.is_empty() {
+				continue;
+			}
+
+			let mut candidate_base = next_free as i32;
+			loop {
+				let fits = children.iter().all(|(c, _)| {
+					let t = (candidate_base + c) as usize;
+					t >= check.len() || check[t] == -1
+				});
+
+				if fits {
+					break;
+				}
+
+				candidate_base += 1;
+			}
+
+			base[state] = candidate_base;
+
+			for (c, child_scratch) in children {
+				let t = (candidate_base + c) as usize;
+
+				if t >= check.len() {
+					let grow = t + 1 - check.len();
+					base.extend(std::iter::repeat(INVALID_BASE).take(grow));
+					check.extend(std::iter::repeat(-1).take(grow));
+					terminal.extend(std::iter::repeat(false).take(grow));
+				}
+
+				check[t] = state as i32;
+				terminal[t] = scratch[child_scratch].is_word;
+
+				mapped[child_scratch] = t;
+				queue.push_back(child_scratch);
+			}
+
+			while next_free < check.len() && check[next_free] != -1 {
+				next_free += 1;
 			}
 		}
+
+		DictTree { base, check, terminal }
 	}
 
 	/**
-	 * Adds a string to the tree
+	 * Walks the array from the root following `string`, returning the
+	 * landing state if every transition along the way is valid
 	 */
-	fn add(&mut self, string: &mut Encoded) {
-		if string.len() > 0 {
-			let index = usize::from(string.remove(0));
+	fn walk(&self, string: &Encoded) -> Option<usize> {
+		let mut state = ROOT;
 
-			match &mut self.children[index] {
-				Some(node) => node.add(string),
-				None => {
-					let children = std::iter::repeat_with(|| None).take(26).collect();
-					self.children[index] = Some(Box::new(Node{children}));
+		for &c in string {
+			let t = (self.base[state] + i32::from(c)) as usize;
 
-					self.children[index].as_mut().unwrap().add(string);
-				}
+			if t >= self.check.len() || self.check[t] != state as i32 {
+				return None;
 			}
+
+			state = t;
 		}
+
+		Some(state)
 	}
-}
 
-impl Serialize for Node {
-	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: serde::Serializer {
-		let mut seq = serializer.serialize_seq(None)?;
+	/**
+	 * Find out if a string exists as a full word in this dictionary tree
+	 */
+	pub fn contains(&self, string: &Encoded) -> bool {
+		match self.walk(string) {
+			Some(state) => self.terminal[state],
+			None => false
+		}
+	}
 
-		// Traverse tree
-		for (i, e) in self.children.iter().enumerate() {
-			seq.serialize_element(e)?
+	/**
+	 * Find out if any dictionary word starts with the given bytes
+	 */
+	pub fn is_prefix(&self, string: &Encoded) -> bool {
+		if string.is_empty() {
+			return true;
 		}
 
-		seq.end()
+		self.walk(string).is_some()
 	}
 }
 
-impl Serialize for DictTree {
-	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: serde::Serializer {
-		self.head.serialize(serializer)
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn word(s: &str) -> Encoded {
+		s.chars().map(|c| c as u8 - b'a').collect()
+	}
+
+	#[test]
+	fn contains_only_full_words() {
+		let dict: Dict = vec![word("cat"), word("car"), word("cartoon")];
+		let tree = DictTree::new(&dict);
+
+		assert!(tree.contains(&word("cat")));
+		assert!(tree.contains(&word("car")));
+		assert!(tree.contains(&word("cartoon")));
+
+		// "ca" and "cart" are prefixes of dictionary words but not words
+		// themselves
+		assert!(!tree.contains(&word("ca")));
+		assert!(!tree.contains(&word("cart")));
+		assert!(!tree.contains(&word("dog")));
+	}
+
+	#[test]
+	fn is_prefix_accepts_any_partial_word() {
+		let dict: Dict = vec![word("cat"), word("car"), word("cartoon")];
+		let tree = DictTree::new(&dict);
+
+		assert!(tree.is_prefix(&word("")));
+		assert!(tree.is_prefix(&word("ca")));
+		assert!(tree.is_prefix(&word("cart")));
+		assert!(tree.is_prefix(&word("cartoo")));
+
+		assert!(!tree.is_prefix(&word("cab")));
+		assert!(!tree.is_prefix(&word("dog")));
+	}
+
+	#[test]
+	fn walking_past_a_leaf_fails_instead_of_landing_on_an_unrelated_cell() {
+		// "cat" is a leaf with no children, so this walks one step past it -
+		// the word this would land on if base defaulted to 0 and slot 0's
+		// coincidental emptiness were ever relied on
+		let dict: Dict = vec![word("cat"), word("car"), word("cartoon")];
+		let tree = DictTree::new(&dict);
+
+		assert!(!tree.is_prefix(&word("cats")));
+		assert!(!tree.contains(&word("cats")));
 	}
 }
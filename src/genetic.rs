@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+
+/**
+ * An evolutionary key search, used as a fallback for noisy ciphertext
+ * where the deterministic AttemptOrder search never reaches the true key
+ */
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use crate::ngram::gen_ngram_freqs;
+use crate::{decrypt_str, Dict, Encoded};
+
+const POPULATION_SIZE: usize = 50;
+const ELITE_FRACTION: f32 = 0.1;
+const MUTATION_RATE: f32 = 0.05;
+const TOURNAMENT_SIZE: usize = 3;
+const MAX_GENERATIONS: usize = 500;
+const TOP_OFFSETS: usize = 3;
+const FLOOR_LOG_PROB: f32 = -10.0;
+const CONFIDENT_FITNESS: f32 = -2.5;
+
+pub struct GeneticSearch {
+	ciphertext: Encoded,
+	pw_len: usize,
+	best_keys: Vec<Vec<u8>>,
+	quadgrams: HashMap<[u8; 4], f32>,
+}
+
+impl GeneticSearch {
+	/**
+	 * Builds a new search over the given ciphertext, using the sorted
+	 * offset guesses from find_best_offsets to seed the population and the
+	 * dictionary to train the quadgram fitness model
+	 */
+	pub fn new(ciphertext: &Encoded, pw_len: usize, best_keys: &Vec<Vec<u8>>, dict: &Dict) -> Self {
+		let quadgrams = gen_ngram_freqs::<4>(dict);
+
+		GeneticSearch {
+			ciphertext: ciphertext.clone(),
+			pw_len,
+			best_keys: best_keys.clone(),
+			quadgrams,
+		}
+	}
+
+	/**
+	 * Evolves the population until its fittest member passes
+	 * CONFIDENT_FITNESS or MAX_GENERATIONS is reached, returning the
+	 * fittest key found
+	 */
+	pub fn run(&self) -> Encoded {
+		let mut population = self.init_population();
+
+		for _ in 0..MAX_GENERATIONS {
+			population.sort_by(|a, b| self.fitness(b).partial_cmp(&self.fitness(a)).unwrap());
+
+			if self.fitness(&population[0]) > CONFIDENT_FITNESS {
+				return population[0].clone();
+			}
+
+			population = self.next_generation(&population);
+		}
+
+		population.sort_by(|a, b| self.fitness(b).partial_cmp(&self.fitness(a)).unwrap());
+		population.swap_remove(0)
+	}
+
+	/**
+	 * Scores a key by the sum of log-frequencies of the quadgrams in its
+	 * decryption, falling back to a floor value for unseen quadgrams
+	 */
+	fn fitness(&self, key: &Encoded) -> f32 {
+		let attempt = decrypt_str(&self.ciphertext, key);
+
+		if attempt.len() < 4 {
+			return FLOOR_LOG_PROB;
+		}
+
+		attempt.windows(4)
+			.map(|w| {
+				let gram = [w[0], w[1], w[2], w[3]];
+				*self.quadgrams.get(&gram).unwrap_or(&FLOOR_LOG_PROB)
+			})
+			.sum()
+	}
+
+	/**
+	 * Seeds a population by sampling each key position from the top few
+	 * offsets in best_keys, with a chance of a fully random perturbation
+	 */
+	fn init_population(&self) -> Vec<Encoded> {
+		let mut rng = rand::thread_rng();
+
+		(0..POPULATION_SIZE).map(|_| {
+			(0..self.pw_len).map(|i| {
+				let top_n = TOP_OFFSETS.min(self.best_keys[i].len());
+
+				if rng.gen_bool(0.8) {
+					self.best_keys[i][rng.gen_range(0..top_n)]
+				} else {
+					rng.gen_range(0..26)
+				}
+			}).collect()
+		}).collect()
+	}
+
+	/**
+	 * Produces the next generation: the elite fraction survives unchanged,
+	 * the rest are filled with tournament-selected, crossed-over, mutated
+	 * children
+	 */
+	fn next_generation(&self, population: &Vec<Encoded>) -> Vec<Encoded> {
+		let elite_count = ((population.len() as f32) * ELITE_FRACTION) as usize;
+		let mut next: Vec<Encoded> = population.iter().take(elite_count.max(1)).cloned().collect();
+
+		while next.len() < population.len() {
+			let parent_a = self.tournament_select(population);
+			let parent_b = self.tournament_select(population);
+			let mut child = self.crossover(parent_a, parent_b);
+
+			self.mutate(&mut child);
+			next.push(child);
+		}
+
+		next
+	}
+
+	/**
+	 * Picks the fittest of TOURNAMENT_SIZE randomly chosen individuals
+	 */
+	fn tournament_select<'a>(&self, population: &'a Vec<Encoded>) -> &'a Encoded {
+		let mut rng = rand::thread_rng();
+
+		population.choose_multiple(&mut rng, TOURNAMENT_SIZE)
+			.max_by(|a, b| self.fitness(a).partial_cmp(&self.fitness(b)).unwrap())
+			.unwrap()
+	}
+
+	/**
+	 * Builds a child key by copying each position from one of the two
+	 * parents at random
+	 */
+	fn crossover(&self, parent_a: &Encoded, parent_b: &Encoded) -> Encoded {
+		let mut rng = rand::thread_rng();
+
+		(0..self.pw_len).map(|i| {
+			if rng.gen_bool(0.5) { parent_a[i] } else { parent_b[i] }
+		}).collect()
+	}
+
+	/**
+	 * Replaces each key position with another offset with small probability
+	 */
+	fn mutate(&self, key: &mut Encoded) {
+		let mut rng = rand::thread_rng();
+
+		for offset in key.iter_mut() {
+			if rng.gen_bool(MUTATION_RATE as f64) {
+				*offset = rng.gen_range(0..26);
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_utils::{encode, encrypt};
+
+	#[test]
+	fn finds_a_short_repeating_key() {
+		let word = encode("mississippi");
+		let dict: Dict = vec![word.clone()];
+
+		let key = encode("cj");
+		let plaintext: Encoded = word.iter().chain(word.iter()).cloned().collect();
+		let ciphertext = encrypt(&plaintext, &key);
+
+		// Seed the offset rankings with the correct offset in front so the
+		// test isn't at the mercy of a cold random search
+		let best_keys: Vec<Vec<u8>> = (0..key.len())
+			.map(|i| {
+				let mut offsets: Vec<u8> = (0..26).collect();
+				offsets.swap(0, key[i] as usize);
+				offsets
+			})
+			.collect();
+
+		let search = GeneticSearch::new(&ciphertext, key.len(), &best_keys, &dict);
+		let found = search.run();
+
+		assert_eq!(decrypt_str(&ciphertext, &found), plaintext);
+	}
+}
+
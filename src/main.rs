@@ -2,13 +2,17 @@
 extern crate test;
 
 pub mod attempt_order;
+pub mod beam;
+pub mod codec;
+pub mod dict_tree;
+pub mod genetic;
+pub mod ngram;
+pub mod scoring;
+#[cfg(test)]
+pub(crate) mod test_utils;
 
-use std::fs::File;
-use std::io::prelude::*;
-use serde_json;
-
-type Encoded = Vec<u8>;
-type Dict = Vec<Encoded>;
+pub(crate) type Encoded = Vec<u8>;
+pub(crate) type Dict = Vec<Encoded>;
 
 /**
  * Flips around the Option by returning None if any element of the input is None
@@ -21,34 +25,6 @@ fn all_or_nothing(input: &Vec<Option<u8>>) -> Option<Encoded> {
 	}
 }
 
-/**
- * Checks if an attempted decryption is a possible solution
- */
-fn check_attempt(attempt: &Encoded, first_word_dict: &Dict) -> bool {
-	let first_word_length = first_word_dict[0].len();
-
-	for word in first_word_dict {
-		let mut word_possible = true;
-
-		for i in 0..first_word_length {
-			let letter = word[i];
-
-			// check if it fails at this letter
-			if letter != attempt[i] {
-				word_possible = false;
-				break;
-			}
-		}
-
-		// If any of the words were possible, just return true
-		if word_possible {
-			return true;
-		}
-	}
-
-	return false;
-}
-
 /**
  * Gets a key from the best keys list and a combination
  */
@@ -88,14 +64,14 @@ fn decode(code: &Encoded) -> String {
 /**
  * Decrypts one u8 character
  */
-fn decrypt_char(input: &u8, key: &u8) -> u8 {
+pub(crate) fn decrypt_char(input: &u8, key: &u8) -> u8 {
 	(input + 26 - key) % 26
 }
 
 /**
  * Decrypts a Vec of u8 characters
  */
-fn decrypt_str(input: &Encoded, key: &Encoded) -> Encoded {
+pub(crate) fn decrypt_str(input: &Encoded, key: &Encoded) -> Encoded {
 	input.iter().enumerate().map(
 		|(i, c)| decrypt_char(c, &key[i % key.len()])
 	).collect()
@@ -252,72 +228,13 @@ fn strip_message(message: &String) -> String {
 	message.to_lowercase().chars().filter(|c| c >= &'a' && c <= &'z').collect()
 }
 
-fn write_dict_freqs(freqs: &Vec<f32>) -> std::io::Result<()> {
-	let mut file = File::create("frequencies.json")?;
-
-	let json = serde_json::to_string(&freqs)?;
-	let mut data: Vec<u8> = Vec::new();
-
-	write!(&mut data, "{}", json)?;
-	file.write(&data)?;
-
-	Ok(())
-}
-
-fn read_dict_freqs() -> std::io::Result<Vec<f32>> {
-	let mut file = File::open("frequencies.json")?;
-
-	let mut data = Vec::<u8>::new();
-	file.read_to_end(&mut data)?;
-
-	let freqs = serde_json::from_slice(&data)?;
-
-	Ok(freqs)
-}
-
-/**
- * Writes the first word dictionary cache files
- */
-fn write_fwds(dict: &Dict, length: usize) -> std::io::Result<Dict> {
-	let mut dicts_by_length = vec![Vec::new();3];
-
-	for word in dict {
-		let len = word.len();
+// Stop the AttemptOrder search early once a candidate's whole-message
+// score clears this bar, instead of requiring an exact first-word hit
+const CONFIDENT_SCORE: f32 = -3.0;
 
-		match dicts_by_length.get_mut(len) {
-			Some(d) => d.push(word.clone()),
-			None => {
-				dicts_by_length.insert(len, Vec::new());
-				dicts_by_length[len].push(word.clone());
-			}
-		};
-	}
-
-	for (i, d) in dicts_by_length.iter().enumerate() {
-		let path = format!("dict{}.json", i);
-		let mut file = File::create(path)?;
-
-		let json = serde_json::to_string(&d)?;
-		let mut data: Vec<u8> = Vec::new();
-		write!(&mut data, "{}", json)?;
-
-		file.write(&data)?;
-	}
-
-	Ok(dicts_by_length[length].clone())
-}
-
-fn read_fwd(length: usize) -> std::io::Result<Dict> {
-	let path = format!("dict{}.json", length);
-	let mut file = File::open(path)?;
-
-	let mut data = Vec::<u8>::new();
-	file.read_to_end(&mut data)?;
-
-	let freqs = serde_json::from_slice(&data)?;
-
-	Ok(freqs)
-}
+// AttemptOrder enumerates every combination, so cap how many candidates
+// the main loop scores before handing off to the beam and genetic searches
+const MAX_ATTEMPTS: usize = 200_000;
 
 fn main() -> std::io::Result<()> {
 	let start = std::time::Instant::now();
@@ -326,35 +243,36 @@ fn main() -> std::io::Result<()> {
 	let dictionary_file = "./dictionary.txt";
 	let raw_ciphertext = "VVVLZWWPBWHZDKBTXLDCGOTGTGRWAQWZSDHEMXLBELUMO".to_string();
 	let pw_len = 7;
-	let first_word_len = 13;
 
 	let ciphertext = encode(&raw_ciphertext);
 
-	// Get first word dictionary
-	let dict_read = read_fwd(first_word_len);
-	let dict = match dict_read {
-		Ok(d) => d,
+	let dict_read = codec::read_dict();
+	let dict: Dict = match dict_read {
+		Ok(dict) => dict,
 		_ => {
 			let raw_dict = get_dictionary(dictionary_file);
-			let dict = &raw_dict.iter().map(|w| encode(w)).collect();
-			write_fwds(dict, first_word_len)?
+			let dict: Dict = raw_dict.iter().map(|w| encode(w)).collect();
+			codec::write_dict(&dict)?;
+
+			dict
 		}
 	};
 
 	// Get letter frequencies
-	let freqs_read = read_dict_freqs();
+	let freqs_read = codec::read_dict_freqs();
 	let dict_freqs = match freqs_read {
 		Ok(freqs) => freqs,
 		_ => {
-			let raw_dict = get_dictionary(dictionary_file);
-			let encoded = raw_dict.iter().map(|w| encode(w)).collect();
-			let freqs = gen_dict_freqs(&encoded);
-			write_dict_freqs(&freqs)?;
+			let freqs = gen_dict_freqs(&dict);
+			codec::write_dict_freqs(&freqs)?;
 
 			freqs
 		}
 	};
 
+	let tree = dict_tree::DictTree::new(&dict);
+	let trigram_freqs = scoring::gen_trigram_freqs(&dict);
+
 	let mut best_keys: Vec<Vec<u8>> = Vec::new();
 
 	for key_part in 0..pw_len {
@@ -364,19 +282,69 @@ fn main() -> std::io::Result<()> {
 		best_keys.push(find_best_offsets(&dict_freqs, &ciphertext_freqs));
 	}
 
-	// Attempt decryption
+	let mut best_key: Option<Encoded> = None;
+	let mut best_attempt: Option<Encoded> = None;
+	let mut best_score = f32::NEG_INFINITY;
+
+	// Attempt decryption, scoring every candidate by how dictionary-like
+	// the whole decrypted message is rather than stopping at the first
+	// first-word hit, so this works without knowing the first word's length
 	let order = attempt_order::AttemptOrder::new(pw_len, 26);
 
-	for combination in order {
+	for combination in order.take(MAX_ATTEMPTS) {
 		let key = choose_key(&best_keys, &combination);
 		let attempt = decrypt_str(&ciphertext, &key);
+		let score = scoring::score(&attempt, &trigram_freqs, &tree);
 
-		if check_attempt(&attempt, &dict) {
-			println!("{} -> {}", decode(&key), decode(&attempt).to_uppercase());
+		if score > best_score {
+			best_score = score;
+			best_key = Some(key);
+			best_attempt = Some(attempt);
+		}
+
+		if best_score > CONFIDENT_SCORE {
 			break;
 		}
 	}
 
+	// The deterministic search above only reaches the true key when the
+	// best-offset guesses are already close to correct. When it doesn't
+	// land a confident match, try pruning the same offset rankings against
+	// the dictionary trie instead of enumerating every combination.
+	if best_score <= CONFIDENT_SCORE {
+		let beam = beam::BeamSearch::new(&ciphertext, pw_len, &best_keys, &tree);
+
+		if let Some(key) = beam.run() {
+			let attempt = decrypt_str(&ciphertext, &key);
+			let score = scoring::score(&attempt, &trigram_freqs, &tree);
+
+			if score > best_score {
+				best_score = score;
+				best_key = Some(key);
+				best_attempt = Some(attempt);
+			}
+		}
+	}
+
+	// If there's still no confident match after the beam search, fall back
+	// to evolving a population of full keys instead.
+	if best_score <= CONFIDENT_SCORE {
+		let search = genetic::GeneticSearch::new(&ciphertext, pw_len, &best_keys, &dict);
+		let key = search.run();
+		let attempt = decrypt_str(&ciphertext, &key);
+		let score = scoring::score(&attempt, &trigram_freqs, &tree);
+
+		if score > best_score {
+			best_score = score;
+			best_key = Some(key);
+			best_attempt = Some(attempt);
+		}
+	}
+
+	let key = best_key.expect("no candidate keys were generated");
+	let attempt = best_attempt.expect("no candidate keys were generated");
+
+	println!("{} -> {} (score {})", decode(&key), decode(&attempt).to_uppercase(), best_score);
 	println!("It took {}ms", start.elapsed().as_millis());
 
 	Ok(())
@@ -455,15 +423,6 @@ mod tests {
 		b.iter(|| decrypt_str(&input, &key))
 	}
 
-	#[bench]
-	fn bench_check_attempt(b: &mut Bencher) {
-		let attempt = encode(&"PSPDYLOAFSGFREQKKPOERNIYVSDZSUOVGXSRRIPWERDIPCFSDIQZIASEJVCGXAYBGYXFPSREKFMEXEBIYDGFKREOWGXEQSXSKXGYRRRVMEKFFIPIWJSKFDJMBGCC".to_string());
-		let filtered_dict = filter_dictionary(&get_dictionary("./dictionary.txt"), 8);
-		let first_word_dict: Dict = filtered_dict.iter().map(|w| encode(w)).collect();
-
-		b.iter(|| check_attempt(&attempt, &first_word_dict))
-	}
-
 	#[bench]
 	fn bench_main(b: &mut Bencher) {
 		b.iter(|| main())
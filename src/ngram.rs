@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+
+/**
+ * A generic letter n-gram log-probability model, shared by the genetic
+ * search's quadgram fitness and the whole-message trigram scorer
+ */
+
+type Encoded = Vec<u8>;
+type Dict = Vec<Encoded>;
+
+/**
+ * Builds a log-probability table of the length-N letter n-grams seen in
+ * the dictionary
+ */
+pub fn gen_ngram_freqs<const N: usize>(dict: &Dict) -> HashMap<[u8; N], f32> {
+	let mut counts: HashMap<[u8; N], u32> = HashMap::new();
+	let mut total: u32 = 0;
+
+	for word in dict {
+		if word.len() < N {
+			continue;
+		}
+
+		for w in word.windows(N) {
+			let gram: [u8; N] = w.try_into().unwrap();
+			*counts.entry(gram).or_insert(0) += 1;
+			total += 1;
+		}
+	}
+
+	counts.iter()
+		.map(|(gram, count)| (*gram, (*count as f32 / total as f32).ln()))
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn scores_repeated_ngrams_higher_than_rare_ones() {
+		let dict: Dict = vec![vec![0, 1, 2], vec![0, 1, 2], vec![3, 4, 5]];
+
+		let freqs = gen_ngram_freqs::<3>(&dict);
+
+		assert!(freqs[&[0u8, 1, 2]] > freqs[&[3u8, 4, 5]]);
+	}
+}
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+/**
+ * Scores a decrypted candidate by how dictionary-like the whole message
+ * is, rather than requiring a first word of a known length to match a
+ * dictionary bucket
+ */
+
+use crate::dict_tree::DictTree;
+use crate::ngram::gen_ngram_freqs;
+
+type Encoded = Vec<u8>;
+type Dict = Vec<Encoded>;
+
+const TRIGRAM_LEN: usize = 3;
+const FLOOR_LOG_PROB: f32 = -12.0;
+const SEGMENTATION_PENALTY: f32 = -6.0;
+
+/**
+ * Builds a log-probability table of the letter trigrams seen in the
+ * dictionary, the sibling of gen_dict_freqs for whole-word n-grams
+ */
+pub fn gen_trigram_freqs(dict: &Dict) -> HashMap<[u8; TRIGRAM_LEN], f32> {
+	gen_ngram_freqs::<TRIGRAM_LEN>(dict)
+}
+
+/**
+ * Scores a plaintext by the mean log-probability of its overlapping
+ * trigrams, falling back to a floor value for trigrams never seen in the
+ * dictionary
+ */
+pub fn score_trigrams(plaintext: &Encoded, freqs: &HashMap<[u8; TRIGRAM_LEN], f32>) -> f32 {
+	if plaintext.len() < TRIGRAM_LEN {
+		return FLOOR_LOG_PROB;
+	}
+
+	let scores: Vec<f32> = plaintext.windows(TRIGRAM_LEN)
+		.map(|w| *freqs.get(&[w[0], w[1], w[2]]).unwrap_or(&FLOOR_LOG_PROB))
+		.collect();
+
+	scores.iter().sum::<f32>() / scores.len() as f32
+}
+
+/**
+ * Greedily segments a plaintext into dictionary words using longest-prefix
+ * matching, penalizing leftover characters that don't tile into a word
+ */
+pub fn score_segmentation(plaintext: &Encoded, tree: &DictTree) -> f32 {
+	if plaintext.is_empty() {
+		return 0.0;
+	}
+
+	let mut score = 0.0;
+	let mut i = 0;
+
+	while i < plaintext.len() {
+		let mut matched = 0;
+
+		for len in (1..=(plaintext.len() - i)).rev() {
+			if tree.contains(&plaintext[i..i + len].to_vec()) {
+				matched = len;
+				break;
+			}
+		}
+
+		if matched > 0 {
+			score += matched as f32;
+			i += matched;
+		} else {
+			score += SEGMENTATION_PENALTY;
+			i += 1;
+		}
+	}
+
+	score / plaintext.len() as f32
+}
+
+/**
+ * Combines the n-gram and segmentation scores into a single fitness value
+ * for a decrypted candidate
+ */
+pub fn score(plaintext: &Encoded, freqs: &HashMap<[u8; TRIGRAM_LEN], f32>, tree: &DictTree) -> f32 {
+	score_trigrams(plaintext, freqs) + score_segmentation(plaintext, tree)
+}
@@ -0,0 +1,20 @@
+/**
+ * Shared plaintext/ciphertext fixture helpers for the search modules' unit
+ * tests, so genetic.rs and beam.rs aren't each carrying their own copy
+ */
+
+use crate::Encoded;
+
+pub(crate) fn encode_char(c: char) -> u8 {
+	c as u8 - b'a'
+}
+
+pub(crate) fn encode(s: &str) -> Encoded {
+	s.chars().map(encode_char).collect()
+}
+
+pub(crate) fn encrypt(plaintext: &Encoded, key: &Encoded) -> Encoded {
+	plaintext.iter().enumerate()
+		.map(|(i, c)| (c + key[i % key.len()]) % 26)
+		.collect()
+}